@@ -0,0 +1,179 @@
+use std::fmt;
+
+use valthrun_kernel_interface::KInterfaceError;
+
+use crate::RenderBackend;
+
+/// Centralizes every controller-level failure class behind a single type so
+/// callers (startup, the update loop) don't each reimplement the mapping
+/// from a low-level error to a user-facing message.
+///
+/// Every variant carries its own troubleshooting link, since "what do I do
+/// about this" is part of what makes an error class distinct.
+#[derive(Debug, Clone)]
+pub enum ControllerError {
+    /// The kernel driver interface could not be found at all.
+    DriverMissing,
+
+    /// A loaded driver exists, but is older than what this controller requires.
+    DriverTooOld {
+        driver_version: String,
+        required_version: String,
+    },
+
+    /// A loaded driver exists, but is newer than what this controller expects.
+    DriverTooNew {
+        driver_version: String,
+        required_version: String,
+    },
+
+    /// The CS2 process could not be found.
+    ProcessNotFound,
+
+    /// The CS2 offsets could not be loaded (likely a CS2 update).
+    OffsetsLoadFailure(String),
+
+    /// The overlay could not load the given rendering backend's library
+    /// (`vulkan-1.dll`, `d3d11.dll`, `opengl32.dll`, ...). Carries the
+    /// backend that failed so the message does not keep blaming Vulkan once
+    /// the DX11/OpenGL fallbacks have also been exhausted.
+    RenderBackendLoadFailure(RenderBackend, String),
+}
+
+impl ControllerError {
+    /// Maps a [`KInterfaceError`] encountered during startup or the update
+    /// loop to its corresponding [`ControllerError`], or `None` if the
+    /// error does not correspond to a known, user-actionable class.
+    pub fn from_kinterface_error(error: &KInterfaceError) -> Option<Self> {
+        Some(match error {
+            KInterfaceError::DeviceUnavailable(error) if error.code().0 as u32 == 0x80070002 => {
+                /* The system cannot find the file specified. */
+                ControllerError::DriverMissing
+            }
+            KInterfaceError::DriverTooOld {
+                driver_version_string,
+                requested_version_string,
+                ..
+            } => ControllerError::DriverTooOld {
+                driver_version: driver_version_string.clone(),
+                required_version: requested_version_string.clone(),
+            },
+            KInterfaceError::DriverTooNew {
+                driver_version_string,
+                requested_version_string,
+                ..
+            } => ControllerError::DriverTooNew {
+                driver_version: driver_version_string.clone(),
+                required_version: requested_version_string.clone(),
+            },
+            KInterfaceError::ProcessDoesNotExists => ControllerError::ProcessNotFound,
+            _ => return None,
+        })
+    }
+
+    /// A short wiki link with troubleshooting steps specific to this error.
+    pub fn troubleshooting_link(&self) -> &'static str {
+        match self {
+            ControllerError::DriverMissing => {
+                "https://wiki.valth.run/troubleshooting/overlay/driver_has_not_been_loaded"
+            }
+            ControllerError::DriverTooOld { .. } | ControllerError::DriverTooNew { .. } => {
+                "https://wiki.valth.run/troubleshooting/overlay/driver_version_mismatch"
+            }
+            ControllerError::ProcessNotFound => {
+                "https://wiki.valth.run/troubleshooting/overlay/process_not_found"
+            }
+            ControllerError::OffsetsLoadFailure(_) => {
+                "https://wiki.valth.run/troubleshooting/overlay/offsets"
+            }
+            ControllerError::RenderBackendLoadFailure(_, _) => {
+                "https://wiki.valth.run/troubleshooting/overlay/vulkan"
+            }
+        }
+    }
+
+    /// Whether this error class can clear itself once the underlying
+    /// condition changes (CS2 restarting, driver reconnecting) without the
+    /// user having to do anything, as opposed to requiring user action
+    /// (e.g. updating the driver).
+    pub fn is_recoverable(&self) -> bool {
+        matches!(
+            self,
+            ControllerError::ProcessNotFound | ControllerError::DriverMissing
+        )
+    }
+}
+
+impl fmt::Display for ControllerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControllerError::DriverMissing => write!(
+                f,
+                "Could not find the kernel driver interface.\nEnsure you have successfully loaded/mapped the kernel driver (valthrun-driver.sys) before starting the CS2 controller."
+            ),
+            ControllerError::DriverTooOld {
+                driver_version,
+                required_version,
+            } => write!(
+                f,
+                "The installed/loaded Valthrun driver version is too old.\nPlease ensure you installed/mapped the latest Valthrun driver.\nATTENTION: If you have manually mapped the driver, you have to restart your PC in order to load the new version.\n\nLoaded driver version: {}\nRequired driver version: {}",
+                driver_version, required_version
+            ),
+            ControllerError::DriverTooNew {
+                driver_version,
+                required_version,
+            } => write!(
+                f,
+                "The installed/loaded Valthrun driver version is too new.\nPlease ensure you're using the lattest controller.\n\nLoaded driver version: {}\nRequired driver version: {}",
+                driver_version, required_version
+            ),
+            ControllerError::ProcessNotFound => write!(
+                f,
+                "Could not find CS2 process.\nPlease start CS2 prior to executing this application!"
+            ),
+            ControllerError::OffsetsLoadFailure(reason) => {
+                write!(f, "Failed to load CS2 offsets: {}", reason)
+            }
+            ControllerError::RenderBackendLoadFailure(backend, reason) => {
+                write!(f, "Failed to load the {:?} rendering backend: {}", backend, reason)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_does_not_exist_maps_to_process_not_found() {
+        let mapped = ControllerError::from_kinterface_error(&KInterfaceError::ProcessDoesNotExists);
+        assert!(matches!(mapped, Some(ControllerError::ProcessNotFound)));
+    }
+
+    #[test]
+    fn process_not_found_and_driver_missing_are_recoverable() {
+        assert!(ControllerError::ProcessNotFound.is_recoverable());
+        assert!(ControllerError::DriverMissing.is_recoverable());
+    }
+
+    #[test]
+    fn offsets_and_backend_load_failures_require_user_action() {
+        assert!(!ControllerError::OffsetsLoadFailure("corrupt offsets".into()).is_recoverable());
+        assert!(!ControllerError::RenderBackendLoadFailure(
+            RenderBackend::Vulkan,
+            "missing dll".into()
+        )
+        .is_recoverable());
+    }
+
+    #[test]
+    fn render_backend_load_failure_names_the_backend_that_actually_failed() {
+        let error =
+            ControllerError::RenderBackendLoadFailure(RenderBackend::OpenGl, "dlopen failed".into());
+        let message = error.to_string();
+
+        assert!(message.contains("OpenGl"));
+        assert!(!message.contains("vulkan-1.dll"));
+    }
+}
@@ -0,0 +1,489 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+};
+
+use anyhow::{
+    Context,
+    Result,
+};
+use windows::{
+    core::HSTRING,
+    Win32::Graphics::DirectWrite::{
+        DWriteCreateFactory,
+        IDWriteFactory,
+        IDWriteFont,
+        IDWriteFontFamily,
+        IDWriteLocalFontFileLoader,
+        DWRITE_FACTORY_TYPE_SHARED,
+        DWRITE_FONT_STRETCH,
+        DWRITE_FONT_STYLE,
+        DWRITE_FONT_WEIGHT,
+    },
+};
+
+/// Describes where a font's glyph data should be sourced from.
+///
+/// Stored as part of [`crate::settings::AppSettings`] and edited through the
+/// settings UI so users are not limited to the bundled Valthrun font.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum FontDescriptor {
+    /// The font shipped with the controller (`resources/Valthrun-Regular.ttf`).
+    Embedded,
+
+    /// A font loaded from an arbitrary file on disk.
+    Path { path: String, index: u32 },
+
+    /// A font resolved by family name against the systems installed fonts.
+    ///
+    /// Resolution happens via DirectWrite on startup. If no matching family
+    /// can be found the embedded font is used instead.
+    Family {
+        name: String,
+        weight: u32,
+        style: FontStyle,
+        stretch: u32,
+    },
+}
+
+impl Default for FontDescriptor {
+    fn default() -> Self {
+        FontDescriptor::Embedded
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum FontStyle {
+    Normal,
+    Oblique,
+    Italic,
+}
+
+/// Bytes for a font, resolved from a [`FontDescriptor`].
+///
+/// `resolve_font_bytes` never fails: when a descriptor can not be resolved (missing
+/// file, unknown family, ...) the embedded font is returned instead so the
+/// overlay always has a usable font to render with.
+pub fn resolve_font_bytes(descriptor: &FontDescriptor) -> Vec<u8> {
+    let result = match descriptor {
+        FontDescriptor::Embedded => return embedded_font_bytes(),
+        FontDescriptor::Path { path, index } => load_font_file(path, *index),
+        FontDescriptor::Family {
+            name,
+            weight,
+            style,
+            stretch,
+        } => resolve_system_family(name, *weight, *style, *stretch),
+    };
+
+    match result {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            log::warn!(
+                "Failed to resolve font {:?}, falling back to embedded font: {:#}",
+                descriptor,
+                err
+            );
+            embedded_font_bytes()
+        }
+    }
+}
+
+fn embedded_font_bytes() -> Vec<u8> {
+    include_bytes!("../resources/Valthrun-Regular.ttf").to_vec()
+}
+
+fn load_font_file(path: &str, index: u32) -> Result<Vec<u8>> {
+    let data =
+        std::fs::read(path).with_context(|| format!("failed to read font file {}", path))?;
+    extract_font_face(&data, index)
+        .with_context(|| format!("failed to select face {} of font file {}", index, path))
+}
+
+/// Extracts a single face out of a font file's bytes.
+///
+/// A plain TrueType/OpenType file only has one face, so `index` must be `0`.
+/// A font collection (`.ttc`/`.otc`) bundles several faces sharing table
+/// data; imgui's `FontConfig` has no way to select a face within one, so
+/// this rebuilds a standalone single-face font from the collection's
+/// `index`'th table directory instead.
+fn extract_font_face(data: &[u8], index: u32) -> Result<Vec<u8>> {
+    const TTC_TAG: u32 = 0x74746366; // 'ttcf'
+
+    if read_u32(data, 0)? != TTC_TAG {
+        anyhow::ensure!(
+            index == 0,
+            "font file is not a collection, but face index {} was requested",
+            index
+        );
+        return Ok(data.to_vec());
+    }
+
+    let num_fonts = read_u32(data, 8)?;
+    anyhow::ensure!(
+        index < num_fonts,
+        "font collection only has {} face(s), requested index {}",
+        num_fonts,
+        index
+    );
+
+    let face_offset = read_u32(data, 12 + (index as usize) * 4)? as usize;
+    let num_tables = read_u16(data, face_offset + 4)? as usize;
+
+    let mut tables = Vec::with_capacity(num_tables);
+    for i in 0..num_tables {
+        let record = face_offset + 12 + i * 16;
+        let tag = data
+            .get(record..record + 4)
+            .context("truncated table record")?;
+        let checksum = read_u32(data, record + 4)?;
+        let offset = read_u32(data, record + 8)? as usize;
+        let length = read_u32(data, record + 12)? as usize;
+        let table_data = data
+            .get(offset..offset + length)
+            .context("table data out of bounds")?;
+
+        tables.push((tag, checksum, table_data));
+    }
+
+    let (search_range, entry_selector, range_shift) = sfnt_search_params(num_tables as u16);
+    let mut out = Vec::new();
+    out.extend_from_slice(&data[face_offset..face_offset + 4]); // sfnt version
+    out.extend_from_slice(&(num_tables as u16).to_be_bytes());
+    out.extend_from_slice(&search_range.to_be_bytes());
+    out.extend_from_slice(&entry_selector.to_be_bytes());
+    out.extend_from_slice(&range_shift.to_be_bytes());
+
+    let mut directory = Vec::with_capacity(num_tables * 16);
+    let mut body = Vec::new();
+    let mut cursor = 12 + num_tables * 16;
+    for (tag, checksum, table_data) in &tables {
+        directory.extend_from_slice(tag);
+        directory.extend_from_slice(&checksum.to_be_bytes());
+        directory.extend_from_slice(&(cursor as u32).to_be_bytes());
+        directory.extend_from_slice(&(table_data.len() as u32).to_be_bytes());
+
+        body.extend_from_slice(table_data);
+        let padding = (4 - (table_data.len() % 4)) % 4;
+        body.extend(std::iter::repeat(0u8).take(padding));
+        cursor += table_data.len() + padding;
+    }
+
+    out.extend_from_slice(&directory);
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+/// `searchRange`/`entrySelector`/`rangeShift`, as defined by the sfnt offset
+/// table format (largest power of two `<= num_tables`, scaled by 16 bytes).
+fn sfnt_search_params(num_tables: u16) -> (u16, u16, u16) {
+    let mut max_pow2 = 1u16;
+    let mut log2 = 0u16;
+    while max_pow2.saturating_mul(2) <= num_tables {
+        max_pow2 *= 2;
+        log2 += 1;
+    }
+
+    let search_range = max_pow2 * 16;
+    (search_range, log2, num_tables * 16 - search_range)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    let bytes = data
+        .get(offset..offset + 4)
+        .context("read past end of font file")?;
+    Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = data
+        .get(offset..offset + 2)
+        .context("read past end of font file")?;
+    Ok(u16::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+fn resolve_system_family(
+    name: &str,
+    weight: u32,
+    style: FontStyle,
+    stretch: u32,
+) -> Result<Vec<u8>> {
+    unsafe {
+        let factory: IDWriteFactory =
+            DWriteCreateFactory(DWRITE_FACTORY_TYPE_SHARED).context("DWriteCreateFactory")?;
+
+        let collection = factory
+            .GetSystemFontCollection(false)
+            .context("GetSystemFontCollection")?;
+
+        let family_name = HSTRING::from(name);
+        let mut family_index = 0u32;
+        let mut exists = windows::Win32::Foundation::BOOL(0);
+        collection
+            .FindFamilyName(&family_name, &mut family_index, &mut exists)
+            .context("FindFamilyName")?;
+
+        if !exists.as_bool() {
+            anyhow::bail!("no system font family named '{}'", name);
+        }
+
+        let family: IDWriteFontFamily = collection
+            .GetFontFamily(family_index)
+            .context("GetFontFamily")?;
+
+        let font: IDWriteFont = family
+            .GetFirstMatchingFont(
+                DWRITE_FONT_WEIGHT(weight as i32),
+                DWRITE_FONT_STRETCH(stretch as i32),
+                to_dwrite_style(style),
+            )
+            .context("GetFirstMatchingFont")?;
+
+        let face = font.CreateFontFace().context("CreateFontFace")?;
+
+        let mut file_count = 1u32;
+        let mut files = [None; 1];
+        face.GetFiles(&mut file_count, Some(files.as_mut_ptr()))
+            .context("GetFiles")?;
+        let file = files[0].take().context("font face has no backing file")?;
+
+        let loader = file.GetLoader().context("GetLoader")?;
+        let local_loader: IDWriteLocalFontFileLoader = loader
+            .cast()
+            .context("font is not backed by a local file")?;
+
+        let mut key_ptr = std::ptr::null();
+        let mut key_size = 0u32;
+        file.GetReferenceKey(&mut key_ptr, &mut key_size)
+            .context("GetReferenceKey")?;
+
+        let key = std::slice::from_raw_parts(key_ptr as *const u8, key_size as usize);
+        let mut path_len = local_loader.GetFilePathLengthFromKey(
+            key.as_ptr() as *const _,
+            key_size,
+        )?;
+        path_len += 1;
+        let mut path_buf = vec![0u16; path_len as usize];
+        local_loader.GetFilePathFromKey(
+            key.as_ptr() as *const _,
+            key_size,
+            &mut path_buf,
+        )?;
+
+        let path_len = path_buf.iter().position(|c| *c == 0).unwrap_or(path_buf.len());
+        let path = String::from_utf16_lossy(&path_buf[..path_len]);
+
+        std::fs::read(&path).with_context(|| format!("failed to read resolved font file {}", path))
+    }
+}
+
+fn to_dwrite_style(style: FontStyle) -> DWRITE_FONT_STYLE {
+    match style {
+        FontStyle::Normal => DWRITE_FONT_STYLE(0),
+        FontStyle::Oblique => DWRITE_FONT_STYLE(1),
+        FontStyle::Italic => DWRITE_FONT_STYLE(2),
+    }
+}
+
+/// A selectable Unicode block to rasterize into the font atlas.
+///
+/// Every block beyond `Latin` costs atlas VRAM, CJK in particular, so users
+/// on low-VRAM systems can opt out of blocks they do not need.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum GlyphBlock {
+    Latin,
+    LatinExtended,
+    Cyrillic,
+    ChineseFull,
+    Japanese,
+    Korean,
+    Thai,
+    Vietnamese,
+}
+
+impl GlyphBlock {
+    /// Whether this block requires merging a CJK fallback font, as the
+    /// embedded Valthrun font does not contain glyphs for it.
+    pub fn requires_cjk_fallback(&self) -> bool {
+        matches!(
+            self,
+            GlyphBlock::ChineseFull | GlyphBlock::Japanese | GlyphBlock::Korean
+        )
+    }
+
+    /// Returns this block's range table as `ImWchar` (`u16`) pairs, matching
+    /// the 16-bit glyph range API `imgui`/the vendored `dear-imgui-rs` fork
+    /// exposes by default (`FontGlyphRanges::data()`/`from_slice()` both
+    /// operate on `&[u16]`, not `u32` - the atlas only carries 32-bit glyphs
+    /// when built with the `freetype`/32-bit glyph feature, which this repo
+    /// does not enable).
+    fn imgui_ranges(&self) -> &'static [u16] {
+        /* imgui's built-in range tables, each terminated with a single 0 */
+        match self {
+            GlyphBlock::Latin => imgui::FontGlyphRanges::default().data(),
+            GlyphBlock::LatinExtended => LATIN_EXTENDED_RANGE,
+            GlyphBlock::Cyrillic => imgui::FontGlyphRanges::cyrillic().data(),
+            GlyphBlock::ChineseFull => imgui::FontGlyphRanges::chinese_full().data(),
+            GlyphBlock::Japanese => imgui::FontGlyphRanges::japanese().data(),
+            GlyphBlock::Korean => imgui::FontGlyphRanges::korean().data(),
+            GlyphBlock::Thai => imgui::FontGlyphRanges::thai().data(),
+            GlyphBlock::Vietnamese => imgui::FontGlyphRanges::vietnamese().data(),
+        }
+    }
+}
+
+/// `U+0100-017F` Latin Extended-A, not covered by imgui's own default range.
+const LATIN_EXTENDED_RANGE: &[u16] = &[0x0100, 0x017F, 0];
+
+thread_local! {
+    /* atlases for CJK blocks in particular are expensive to build, so the merged */
+    /* range table for a given block selection is only built once and reused. */
+    static GLYPH_RANGE_CACHE: RefCell<HashMap<Vec<GlyphBlock>, &'static [u16]>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Build (and cache) the combined glyph range table for the given blocks.
+///
+/// The result is reused across calls with the same block selection so
+/// switching the settings window open/closed does not rebuild the ranges
+/// every time.
+pub fn glyph_ranges_for(blocks: &[GlyphBlock]) -> imgui::FontGlyphRanges {
+    let mut key = blocks.to_vec();
+    /* Latin is load-bearing: without it nothing renders, including the
+     * settings window itself, so it's included regardless of the caller's
+     * selection (e.g. an older settings file persisted before this field
+     * existed, or one where the user unchecked every block). */
+    key.push(GlyphBlock::Latin);
+    key.sort();
+    key.dedup();
+
+    let combined = GLYPH_RANGE_CACHE.with(|cache| {
+        if let Some(existing) = cache.borrow().get(&key) {
+            return *existing;
+        }
+
+        let mut combined = Vec::new();
+        for block in &key {
+            let ranges = block.imgui_ranges();
+            /* drop the trailing 0 terminator of each table before merging */
+            combined.extend_from_slice(&ranges[..ranges.len().saturating_sub(1)]);
+        }
+        combined.push(0);
+
+        let leaked: &'static [u16] = Box::leak(combined.into_boxed_slice());
+        cache.borrow_mut().insert(key.clone(), leaked);
+        leaked
+    });
+
+    imgui::FontGlyphRanges::from_slice(combined)
+}
+
+/// Bytes for the bundled CJK fallback font, merged into every font whose
+/// glyph blocks require one. The font is embedded at compile time, so this
+/// always returns `Some`.
+pub fn cjk_fallback_font_bytes() -> Option<&'static [u8]> {
+    Some(include_bytes!("../resources/Fallback-CJK.ttf"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyph_ranges_for_dedupes_and_ignores_selection_order() {
+        let a = glyph_ranges_for(&[GlyphBlock::Cyrillic, GlyphBlock::Latin]);
+        let b = glyph_ranges_for(&[GlyphBlock::Latin, GlyphBlock::Cyrillic, GlyphBlock::Latin]);
+        assert_eq!(a.data(), b.data());
+    }
+
+    #[test]
+    fn glyph_ranges_for_reuses_the_cached_table_for_the_same_selection() {
+        let a = glyph_ranges_for(&[GlyphBlock::Thai]);
+        let b = glyph_ranges_for(&[GlyphBlock::Thai]);
+        assert_eq!(a.data().as_ptr(), b.data().as_ptr());
+    }
+
+    #[test]
+    fn glyph_ranges_for_merges_blocks_with_a_single_trailing_terminator() {
+        let combined = glyph_ranges_for(&[GlyphBlock::Latin, GlyphBlock::Cyrillic]);
+        let data = combined.data();
+
+        assert_eq!(*data.last().unwrap(), 0);
+        assert!(data[..data.len() - 1]
+            .chunks(2)
+            .all(|pair| pair != [0, 0]));
+    }
+
+    /// Builds a minimal, syntactically valid TrueType Collection containing
+    /// `faces.len()` single-table faces, one table per face with the given
+    /// tag and payload.
+    fn build_synthetic_ttc(faces: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let num_fonts = faces.len() as u32;
+        let mut out = Vec::new();
+
+        // TTC header: tag, version, numFonts, then one face-offset slot per face.
+        out.extend_from_slice(b"ttcf");
+        out.extend_from_slice(&1u32.to_be_bytes());
+        out.extend_from_slice(&num_fonts.to_be_bytes());
+        let offset_slots_at = out.len();
+        out.extend(std::iter::repeat(0u8).take(faces.len() * 4));
+
+        let mut face_offsets = Vec::with_capacity(faces.len());
+        for _ in faces {
+            face_offsets.push(out.len() as u32);
+            // sfnt version, numTables = 1, searchRange/entrySelector/rangeShift (unused by the parser).
+            out.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+            out.extend_from_slice(&1u16.to_be_bytes());
+            out.extend_from_slice(&0u16.to_be_bytes());
+            out.extend_from_slice(&0u16.to_be_bytes());
+            out.extend_from_slice(&0u16.to_be_bytes());
+            // Table record, patched in once the payload offset is known.
+            out.extend(std::iter::repeat(0u8).take(16));
+        }
+
+        for (i, (tag, payload)) in faces.iter().enumerate() {
+            let payload_offset = out.len() as u32;
+            out.extend_from_slice(payload);
+
+            let record_at = (face_offsets[i] + 12) as usize;
+            out[record_at..record_at + 4].copy_from_slice(*tag);
+            out[record_at + 4..record_at + 8].copy_from_slice(&0u32.to_be_bytes());
+            out[record_at + 8..record_at + 12].copy_from_slice(&payload_offset.to_be_bytes());
+            out[record_at + 12..record_at + 16].copy_from_slice(&(payload.len() as u32).to_be_bytes());
+        }
+
+        for (i, offset) in face_offsets.iter().enumerate() {
+            let at = offset_slots_at + i * 4;
+            out[at..at + 4].copy_from_slice(&offset.to_be_bytes());
+        }
+
+        out
+    }
+
+    #[test]
+    fn extract_font_face_round_trips_each_face_of_a_synthetic_ttc() {
+        let ttc = build_synthetic_ttc(&[(b"TST0", b"AAAA"), (b"TST1", b"BBBB")]);
+
+        let face0 = extract_font_face(&ttc, 0).expect("face 0 to extract");
+        assert_eq!(read_u32(&face0, 0).unwrap(), 0x0001_0000);
+        assert_eq!(read_u16(&face0, 4).unwrap(), 1);
+        assert_eq!(&face0[12..16], b"TST0");
+        let table_offset = read_u32(&face0, 20).unwrap() as usize;
+        let table_length = read_u32(&face0, 24).unwrap() as usize;
+        assert_eq!(&face0[table_offset..table_offset + table_length], b"AAAA");
+
+        let face1 = extract_font_face(&ttc, 1).expect("face 1 to extract");
+        assert_eq!(&face1[12..16], b"TST1");
+        let table_offset = read_u32(&face1, 20).unwrap() as usize;
+        let table_length = read_u32(&face1, 24).unwrap() as usize;
+        assert_eq!(&face1[table_offset..table_offset + table_length], b"BBBB");
+    }
+
+    #[test]
+    fn extract_font_face_rejects_an_out_of_range_index() {
+        let ttc = build_synthetic_ttc(&[(b"TST0", b"AAAA")]);
+        assert!(extract_font_face(&ttc, 1).is_err());
+    }
+}
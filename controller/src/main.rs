@@ -3,10 +3,12 @@
 
 use std::{
     cell::{
+        Cell,
         Ref,
         RefCell,
         RefMut,
     },
+    collections::HashMap,
     error::Error,
     fmt::Debug,
     fs::File,
@@ -98,12 +100,23 @@ use crate::{
 
 mod cache;
 mod enhancements;
+mod error;
+mod fonts;
 mod radar;
 mod settings;
 mod utils;
 mod view;
 mod winver;
 
+use crate::error::ControllerError;
+use crate::fonts::{
+    cjk_fallback_font_bytes,
+    glyph_ranges_for,
+    resolve_font_bytes,
+    FontDescriptor,
+    GlyphBlock,
+};
+
 pub trait MetricsClient {
     fn add_metrics_record(&self, record_type: &str, record_payload: &str);
 }
@@ -140,8 +153,103 @@ pub struct UpdateContext<'a> {
     pub cs2: &'a Arc<CS2Handle>,
 }
 
+/// Name used for the default/watermark font when no other name is requested.
+pub const FONT_DEFAULT: &str = "default";
+
 pub struct AppFonts {
-    valthrun: FontId,
+    fonts: HashMap<String, FontId>,
+}
+
+impl AppFonts {
+    /// Resolve a named font, falling back to the default font if no font
+    /// has been registered under that name (e.g. the user removed it from
+    /// the font configuration).
+    pub fn get(&self, name: &str) -> FontId {
+        self.fonts
+            .get(name)
+            .or_else(|| self.fonts.get(FONT_DEFAULT))
+            .copied()
+            .expect("default font to always be present")
+    }
+}
+
+/// An enhancement paired with a stable name used for tracing spans and the
+/// frame timing table, since `dyn Enhancement` alone carries no identity.
+pub struct NamedEnhancement {
+    pub name: &'static str,
+    pub instance: Rc<RefCell<dyn Enhancement>>,
+}
+
+/// Average/last update & render cost for a single enhancement, refreshed
+/// every frame and shown in the frame timing table.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnhancementTiming {
+    pub last_update: Duration,
+    pub avg_update: Duration,
+    pub last_render: Duration,
+    pub avg_render: Duration,
+}
+
+impl EnhancementTiming {
+    fn record_update(&mut self, duration: Duration) {
+        self.last_update = duration;
+        self.avg_update = ewma(self.avg_update, duration);
+    }
+
+    fn record_render(&mut self, duration: Duration) {
+        self.last_render = duration;
+        self.avg_render = ewma(self.avg_render, duration);
+    }
+}
+
+/// Exponentially weighted moving average, so the table reflects recent
+/// frames without being a single-frame snapshot.
+fn ewma(previous: Duration, sample: Duration) -> Duration {
+    if previous.is_zero() {
+        sample
+    } else {
+        previous.mul_f64(0.9) + sample.mul_f64(0.1)
+    }
+}
+
+#[cfg(test)]
+mod ewma_tests {
+    use super::*;
+
+    #[test]
+    fn first_sample_is_taken_verbatim() {
+        assert_eq!(
+            ewma(Duration::ZERO, Duration::from_millis(5)),
+            Duration::from_millis(5)
+        );
+    }
+
+    #[test]
+    fn subsequent_samples_are_weighted_ninety_ten() {
+        let result = ewma(Duration::from_millis(10), Duration::from_millis(20));
+        assert_eq!(result, Duration::from_millis(11));
+    }
+
+    #[test]
+    fn a_single_spike_only_nudges_the_average() {
+        let mut avg = Duration::from_millis(1);
+        for _ in 0..5 {
+            avg = ewma(avg, Duration::from_millis(1));
+        }
+
+        let after_spike = ewma(avg, Duration::from_millis(100));
+        assert!(after_spike < Duration::from_millis(100));
+        assert!(after_spike > Duration::from_millis(1));
+    }
+}
+
+/// Tracks whether the last frame's state differs from the current one, so
+/// the overlay only re-renders (and the GPU only does work) when something
+/// actually changed on screen.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DamageState {
+    last_hash: Option<u64>,
+    last_redraw: Option<Instant>,
 }
 
 pub struct Application {
@@ -149,7 +257,9 @@ pub struct Application {
     pub app_state: StateRegistry,
 
     pub cs2: Arc<CS2Handle>,
-    pub enhancements: Vec<Rc<RefCell<dyn Enhancement>>>,
+    pub enhancements: Vec<NamedEnhancement>,
+    pub enhancement_timings: RefCell<HashMap<&'static str, EnhancementTiming>>,
+    pub last_timings_metric_emit: Cell<Option<Instant>>,
 
     pub frame_read_calls: usize,
     pub last_total_read_calls: usize,
@@ -160,6 +270,14 @@ pub struct Application {
     pub settings_screen_capture_changed: AtomicBool,
     pub settings_render_debug_window_changed: AtomicBool,
 
+    pub damage_state: Cell<DamageState>,
+
+    /// The current recoverable controller error, if any. Surfaced via a
+    /// persistent panel in [`Application::render`] and cleared automatically
+    /// once the update loop succeeds again (e.g. CS2 restarted, driver
+    /// reconnected).
+    pub controller_error: RefCell<Option<ControllerError>>,
+
     pub web_radar: RefCell<Option<Arc<Mutex<WebRadar>>>>,
 }
 
@@ -221,7 +339,7 @@ impl Application {
     pub fn update(&mut self, ui: &imgui::Ui) -> anyhow::Result<()> {
         {
             for enhancement in self.enhancements.iter() {
-                let mut hack = enhancement.borrow_mut();
+                let mut hack = enhancement.instance.borrow_mut();
                 if hack.update_settings(ui, &mut *self.settings_mut())? {
                     self.settings_dirty = true;
                 }
@@ -255,14 +373,48 @@ impl Application {
         };
 
         for enhancement in self.enhancements.iter() {
-            let mut hack = enhancement.borrow_mut();
-            hack.update(&update_context)?;
+            let name = enhancement.name;
+            let span = tracing::debug_span!("enhancement_update", enhancement = name);
+            let _guard = span.enter();
+
+            let started = Instant::now();
+            let mut hack = enhancement.instance.borrow_mut();
+            let result = hack.update(&update_context);
+            drop(hack);
+
+            self.enhancement_timings
+                .borrow_mut()
+                .entry(name)
+                .or_default()
+                .record_update(started.elapsed());
+
+            result?;
         }
 
         let read_calls = self.cs2.ke_interface.total_read_calls();
         self.frame_read_calls = read_calls - self.last_total_read_calls;
         self.last_total_read_calls = read_calls;
 
+        const METRICS_EMIT_INTERVAL: Duration = Duration::from_secs(1);
+        let due_for_metrics_emit = self
+            .last_timings_metric_emit
+            .get()
+            .map(|last| last.elapsed() >= METRICS_EMIT_INTERVAL)
+            .unwrap_or(true);
+
+        if self.settings().valthrun_watermark && due_for_metrics_emit {
+            self.last_timings_metric_emit.set(Some(Instant::now()));
+
+            let timings = self.enhancement_timings.borrow();
+            let record: HashMap<&str, f64> = timings
+                .iter()
+                .map(|(name, timing)| (*name, timing.avg_update.as_secs_f64() * 1000.0))
+                .collect();
+            if let Ok(record) = serde_json::to_string(&record) {
+                self.cs2.add_metrics_record("enhancement-timings", &record);
+            }
+        }
+
         Ok(())
     }
 
@@ -277,21 +429,54 @@ impl Application {
 
         {
             for enhancement in self.enhancements.iter() {
-                let mut enhancement = enhancement.borrow_mut();
-                enhancement.render_debug_window(&self.app_state, ui);
+                let mut instance = enhancement.instance.borrow_mut();
+                instance.render_debug_window(&self.app_state, ui);
             }
         }
 
+        self.render_controller_error(ui);
+
         if self.settings_visible {
             let mut settings_ui = self.settings_ui.borrow_mut();
             settings_ui.render(self, ui)
         }
     }
 
+    /// Renders a persistent panel for the current controller error (if any)
+    /// so a transient disconnect (CS2 restarting, driver reconnecting) is
+    /// visible to the user instead of the overlay silently freezing.
+    fn render_controller_error(&self, ui: &imgui::Ui) {
+        let Some(error) = self.controller_error.borrow().clone() else {
+            return;
+        };
+
+        ui.window("controller-error")
+            .no_decoration()
+            .always_auto_resize(true)
+            .position_pivot([0.5, 0.0])
+            .position(
+                [ui.io().display_size[0] * 0.5, 10.0],
+                Condition::Always,
+            )
+            .build(|| {
+                ui.text_colored([1.0, 0.4, 0.4, 1.0], format!("{}", error));
+                ui.text_disabled(error.troubleshooting_link());
+
+                if error.is_recoverable() {
+                    ui.text_disabled("Waiting for recovery...");
+                }
+            });
+    }
+
     fn render_overlay(&self, ui: &imgui::Ui) {
         let settings = self.settings();
 
         if settings.valthrun_watermark {
+            /* Render under the configured default font rather than whatever
+             * imgui happens to have active, so the user's font/glyph-block
+             * choice for FONT_DEFAULT is actually visible somewhere. */
+            let font_token = ui.push_font(self.fonts.get(FONT_DEFAULT));
+
             {
                 let text_buf;
                 let text = obfstr!(text_buf = "Valthrun Overlay");
@@ -318,15 +503,152 @@ impl Application {
                 ]);
                 ui.text(text)
             }
+
+            self.render_timing_table(ui);
+            font_token.pop();
         }
 
-        for hack in self.enhancements.iter() {
-            let hack = hack.borrow();
-            if let Err(err) = hack.render(&self.app_state, ui) {
+        for enhancement in self.enhancements.iter() {
+            let name = enhancement.name;
+            let span = tracing::debug_span!("enhancement_render", enhancement = name);
+            let _guard = span.enter();
+
+            let started = Instant::now();
+            let hack = enhancement.instance.borrow();
+            let result = hack.render(&self.app_state, ui);
+            drop(hack);
+
+            self.enhancement_timings
+                .borrow_mut()
+                .entry(name)
+                .or_default()
+                .record_render(started.elapsed());
+
+            if let Err(err) = result {
                 log::error!("{:?}", err);
             }
         }
     }
+
+    /// Renders a small per-enhancement timing table below the watermark,
+    /// so users can see which enhancement is costing frame time.
+    fn render_timing_table(&self, ui: &imgui::Ui) {
+        let timings = self.enhancement_timings.borrow();
+        if timings.is_empty() {
+            return;
+        }
+
+        let mut offset = 52.0;
+        for enhancement in self.enhancements.iter() {
+            let Some(timing) = timings.get(enhancement.name) else {
+                continue;
+            };
+
+            let text = format!(
+                "{}: {:.2}ms upd (avg {:.2}ms) / {:.2}ms rnd (avg {:.2}ms)",
+                enhancement.name,
+                timing.last_update.as_secs_f64() * 1000.0,
+                timing.avg_update.as_secs_f64() * 1000.0,
+                timing.last_render.as_secs_f64() * 1000.0,
+                timing.avg_render.as_secs_f64() * 1000.0
+            );
+            ui.set_cursor_pos([
+                ui.window_size()[0] - ui.calc_text_size(&text)[0] - 10.0,
+                offset,
+            ]);
+            ui.text(text);
+            offset += 14.0;
+        }
+    }
+
+    /// Decide whether this frame actually needs to be rendered.
+    ///
+    /// When `redraw_only_on_change` is enabled, a frame is only rendered if
+    /// the cheap state hash below changed, ImGui captured input, or the
+    /// minimum refresh interval elapsed. This lets the overlay sit idle
+    /// (skipping `app.render`) instead of pinning the GPU every frame.
+    pub fn should_redraw(&self, ui: &imgui::Ui) -> bool {
+        let settings = self.settings();
+        if !settings.redraw_only_on_change {
+            return true;
+        }
+
+        let io = ui.io();
+        let input_activity = io.mouse_down.iter().any(|down| *down)
+            || io.mouse_wheel != 0.0
+            || io.want_text_input
+            || ui.is_any_mouse_down()
+            || io.keys_down.iter().any(|down| *down);
+
+        let hash = self.compute_damage_hash(ui);
+        let mut state = self.damage_state.get();
+        let state_changed = state.last_hash != Some(hash);
+
+        if state_changed || input_activity {
+            state.last_hash = Some(hash);
+            state.last_redraw = Some(Instant::now());
+            self.damage_state.set(state);
+            return true;
+        }
+
+        let min_interval = settings
+            .max_fps
+            .filter(|fps| *fps > 0)
+            .map(|fps| Duration::from_secs_f64(1.0 / fps as f64));
+
+        /* with no max-FPS cap configured, an unchanged frame must stay un-redrawn -
+        otherwise "only redraw on change" would be a no-op for every user who hasn't
+        explicitly set a cap, which is the opposite of what this setting promises */
+        let due = match (min_interval, state.last_redraw) {
+            (Some(interval), Some(last_redraw)) => last_redraw.elapsed() >= interval,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if due {
+            state.last_redraw = Some(Instant::now());
+            self.damage_state.set(state);
+        }
+
+        due
+    }
+
+    /// Cheap hash over the state that visibly affects the overlay: resolved
+    /// entity/bomb state, UI visibility and current mouse state. Any change
+    /// here means the previous frame's rendered image is stale.
+    ///
+    /// Deliberately does NOT hash `frame_read_calls`: the number of memory
+    /// reads performed is essentially constant regardless of whether the
+    /// underlying entities actually moved, so it can't stand in for an
+    /// actual game-state delta.
+    fn compute_damage_hash(&self, ui: &imgui::Ui) -> u64 {
+        use std::hash::{
+            Hash,
+            Hasher,
+        };
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+
+        self.settings_visible.hash(&mut hasher);
+        /* the error panel appearing/disappearing is a visible change too */
+        self.controller_error.borrow().is_some().hash(&mut hasher);
+
+        /* resolved the same way as every other per-frame state (BuildInfo, CS2Offsets,
+        ViewController, ...): lazily via the state registry, invalidated every frame */
+        if let Ok(entities) = self.app_state.resolve::<cs2::StateEntityList>(()) {
+            format!("{:?}", *entities).hash(&mut hasher);
+        }
+        if let Ok(bomb) = self.app_state.resolve::<cs2::StateBombState>(()) {
+            format!("{:?}", *bomb).hash(&mut hasher);
+        }
+
+        let io = ui.io();
+        io.mouse_pos[0].to_bits().hash(&mut hasher);
+        io.mouse_pos[1].to_bits().hash(&mut hasher);
+        io.mouse_down.hash(&mut hasher);
+
+        hasher.finish()
+    }
 }
 
 fn show_critical_error(message: &str) {
@@ -348,13 +670,15 @@ fn main() {
         }
     };
 
-    env_logger::builder()
-        .filter_level(if args.verbose {
-            log::LevelFilter::Trace
-        } else {
-            log::LevelFilter::Info
-        })
-        .parse_default_env()
+    /* bridge `log` macros (used throughout the enhancement crates) into the tracing pipeline */
+    tracing_log::LogTracer::init().expect("failed to install the log tracer");
+
+    let default_filter = if args.verbose { "trace" } else { "info" };
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(default_filter)),
+        )
         .init();
 
     let runtime = runtime::Builder::new_multi_thread()
@@ -428,6 +752,57 @@ fn main_schema_dump(args: &SchemaDumpArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Overlay rendering backend, selectable in [`AppSettings`] and auto-detected
+/// on startup when Vulkan is unavailable (old GPUs, VMs without an ICD).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum RenderBackend {
+    Vulkan,
+    Dx11,
+    OpenGl,
+}
+
+impl RenderBackend {
+    /// Next backend to try if this one fails to load, in order of
+    /// decreasing preference. `None` once all backends have been tried.
+    fn fallback(self) -> Option<RenderBackend> {
+        match self {
+            RenderBackend::Vulkan => Some(RenderBackend::Dx11),
+            RenderBackend::Dx11 => Some(RenderBackend::OpenGl),
+            RenderBackend::OpenGl => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod render_backend_tests {
+    use super::*;
+
+    #[test]
+    fn fallback_chain_goes_vulkan_then_dx11_then_opengl() {
+        assert_eq!(RenderBackend::Vulkan.fallback(), Some(RenderBackend::Dx11));
+        assert_eq!(RenderBackend::Dx11.fallback(), Some(RenderBackend::OpenGl));
+    }
+
+    #[test]
+    fn opengl_is_the_last_resort() {
+        assert_eq!(RenderBackend::OpenGl.fallback(), None);
+    }
+}
+
+/// Extracts the underlying library-load failure from an [`OverlayError`],
+/// regardless of which backend (Vulkan, DX11, OpenGL) it came from. Each
+/// backend fails with its own `OverlayError` variant, so the fallback loop
+/// in `main_overlay` must check all of them, not just Vulkan's, or the
+/// DX11 -> OpenGL hop never triggers.
+fn backend_library_load_failure(error: &OverlayError) -> Option<&libloading::Error> {
+    match error {
+        OverlayError::VulkanDllNotFound(LoadingError::LibraryLoadFailure(source)) => Some(source),
+        OverlayError::Dx11DllNotFound(LoadingError::LibraryLoadFailure(source)) => Some(source),
+        OverlayError::OpenGlDllNotFound(LoadingError::LibraryLoadFailure(source)) => Some(source),
+        _ => None,
+    }
+}
+
 fn preload_vulkan_with_act_ctx() -> anyhow::Result<()> {
     unsafe {
         let mut act_ctx = mem::zeroed::<ACTCTXA>();
@@ -473,47 +848,17 @@ fn main_overlay() -> anyhow::Result<()> {
     let cs2 = match CS2Handle::create(settings.metrics) {
         Ok(handle) => handle,
         Err(err) => {
-            if let Some(err) = err.downcast_ref::<KInterfaceError>() {
-                if let KInterfaceError::DeviceUnavailable(error) = &err {
-                    if error.code().0 as u32 == 0x80070002 {
-                        /* The system cannot find the file specified. */
-                        show_critical_error(obfstr!("** PLEASE READ CAREFULLY **\nCould not find the kernel driver interface.\nEnsure you have successfully loaded/mapped the kernel driver (valthrun-driver.sys) before starting the CS2 controller.\n\nFor more help, checkout:\nhttps://wiki.valth.run/troubleshooting/overlay/driver_has_not_been_loaded."));
-                        return Ok(());
-                    }
-                } else if let KInterfaceError::DriverTooOld {
-                    driver_version_string,
-                    requested_version_string,
-                    ..
-                } = &err
-                {
-                    let message = obfstr!(
-                        "\nThe installed/loaded Valthrun driver version is too old.\nPlease ensure you installed/mapped the latest Valthrun driver.\nATTENTION: If you have manually mapped the driver, you have to restart your PC in order to load the new version."
-                    ).to_string();
-
-                    show_critical_error(&format!(
-                        "{}\n\nLoaded driver version: {}\nRequired driver version: {}",
-                        message, driver_version_string, requested_version_string
-                    ));
-                    return Ok(());
-                } else if let KInterfaceError::DriverTooNew {
-                    driver_version_string,
-                    requested_version_string,
-                    ..
-                } = &err
-                {
-                    let message = obfstr!(
-                        "\nThe installed/loaded Valthrun driver version is too new.\nPlease ensure you're using the lattest controller."
-                    ).to_string();
-
-                    show_critical_error(&format!(
-                        "{}\n\nLoaded driver version: {}\nRequired driver version: {}",
-                        message, driver_version_string, requested_version_string
-                    ));
-                    return Ok(());
-                } else if let KInterfaceError::ProcessDoesNotExists = &err {
-                    show_critical_error(obfstr!("Could not find CS2 process.\nPlease start CS2 prior to executing this application!"));
-                    return Ok(());
-                }
+            let controller_error = err
+                .downcast_ref::<KInterfaceError>()
+                .and_then(ControllerError::from_kinterface_error);
+
+            if let Some(controller_error) = controller_error {
+                show_critical_error(&format!(
+                    "{}\n\nFor more help, checkout:\n{}",
+                    controller_error,
+                    controller_error.troubleshooting_link()
+                ));
+                return Ok(());
             }
 
             return Err(err);
@@ -546,62 +891,140 @@ fn main_overlay() -> anyhow::Result<()> {
         );
     }
 
-    offsets_runtime::setup_provider(&cs2)?;
-    app_state
-        .resolve::<CS2Offsets>(())
-        .with_context(|| obfstr!("failed to load CS2 offsets").to_string())?;
+    let offsets_result = offsets_runtime::setup_provider(&cs2)
+        .and_then(|_| app_state.resolve::<CS2Offsets>(()).map(|_| ()));
+    if let Err(err) = offsets_result {
+        let controller_error = ControllerError::OffsetsLoadFailure(format!("{:#}", err));
+        show_critical_error(&format!(
+            "{}\n\nFor more help, checkout:\n{}",
+            controller_error,
+            controller_error.troubleshooting_link()
+        ));
+        return Ok(());
+    }
 
     log::debug!("Initialize overlay");
     let app_fonts: Rc<RefCell<Option<AppFonts>>> = Default::default();
-    let overlay_options = OverlayOptions {
+    let startup_settings = app_state.resolve::<AppSettings>(())?;
+    let mut render_backend = startup_settings
+        .render_backend
+        .unwrap_or(RenderBackend::Vulkan);
+    let mut overlay_options = OverlayOptions {
         title: obfstr!("CS2 Overlay").to_string(),
         target: OverlayTarget::WindowOfProcess(cs2.process_id() as u32),
+        backend: render_backend,
         font_init: Some(Box::new({
             let app_fonts = app_fonts.clone();
+            let font_settings = startup_settings.fonts.clone();
+            let glyph_blocks = startup_settings.glyph_blocks.clone();
 
             move |imgui| {
                 let mut app_fonts = app_fonts.borrow_mut();
 
                 let font_size = 18.0;
-                let valthrun_font = imgui.fonts().add_font(&[FontSource::TtfData {
-                    data: include_bytes!("../resources/Valthrun-Regular.ttf"),
-                    size_pixels: font_size,
-                    config: Some(FontConfig {
-                        rasterizer_multiply: 1.5,
-                        oversample_h: 4,
-                        oversample_v: 4,
-                        ..FontConfig::default()
-                    }),
-                }]);
-
-                *app_fonts = Some(AppFonts {
-                    valthrun: valthrun_font,
-                });
+                let mut fonts = HashMap::new();
+                let glyph_ranges = glyph_ranges_for(&glyph_blocks);
+                let needs_cjk_fallback = glyph_blocks.iter().any(GlyphBlock::requires_cjk_fallback);
+
+                /* always register the embedded font under the default name as a guaranteed fallback */
+                let default_descriptor = font_settings
+                    .get(FONT_DEFAULT)
+                    .cloned()
+                    .unwrap_or(FontDescriptor::Embedded);
+
+                for (name, descriptor) in
+                    std::iter::once((FONT_DEFAULT.to_string(), default_descriptor))
+                        .chain(font_settings.iter().map(|(name, descriptor)| (name.clone(), descriptor.clone())))
+                {
+                    if fonts.contains_key(&name) {
+                        continue;
+                    }
+
+                    let data = resolve_font_bytes(&descriptor);
+                    let font_id = imgui.fonts().add_font(&[FontSource::TtfData {
+                        data: Box::leak(data.into_boxed_slice()),
+                        size_pixels: font_size,
+                        config: Some(FontConfig {
+                            rasterizer_multiply: 1.5,
+                            oversample_h: 4,
+                            oversample_v: 4,
+                            glyph_ranges: glyph_ranges.clone(),
+                            ..FontConfig::default()
+                        }),
+                    }]);
+
+                    /* Merge the (expensive) CJK fallback exactly once into the shared atlas,
+                    right after the default font, instead of once per named font: rasterizing
+                    it again for every configured font would multiply its cost N times over
+                    for no benefit, since imgui's merge mode only extends the font it follows. */
+                    if needs_cjk_fallback && name == FONT_DEFAULT {
+                        if let Some(fallback_data) = cjk_fallback_font_bytes() {
+                            imgui.fonts().add_font(&[FontSource::TtfData {
+                                data: fallback_data,
+                                size_pixels: font_size,
+                                config: Some(FontConfig {
+                                    rasterizer_multiply: 1.5,
+                                    oversample_h: 4,
+                                    oversample_v: 4,
+                                    glyph_ranges: glyph_ranges.clone(),
+                                    merge_mode: true,
+                                    ..FontConfig::default()
+                                }),
+                            }]);
+                        }
+                    }
+
+                    fonts.insert(name, font_id);
+                }
+
+                *app_fonts = Some(AppFonts { fonts });
             }
         })),
     };
 
-    let mut overlay = match overlay::init(&overlay_options) {
-        Err(OverlayError::VulkanDllNotFound(LoadingError::LibraryLoadFailure(source))) => {
-            match &source {
-                libloading::Error::LoadLibraryExW { .. } => {
-                    let error = source.source().context("LoadLibraryExW to have a source")?;
-                    let message = format!("Failed to load vulkan-1.dll.\nError: {:#}", error);
-                    show_critical_error(&message);
-                }
-                error => {
-                    let message = format!(
-                        "An error occurred while loading vulkan-1.dll.\nError: {:#}",
-                        error
+    let mut overlay = loop {
+        match overlay::init(&overlay_options) {
+            Err(err) => {
+                let Some(source) = backend_library_load_failure(&err) else {
+                    return Err(err.into());
+                };
+
+                if let Some(fallback) = render_backend.fallback() {
+                    log::warn!(
+                        "Failed to load the {:?} backend ({:#}). Falling back to the {:?} backend.",
+                        render_backend,
+                        source,
+                        fallback
                     );
-                    show_critical_error(&message);
+                    render_backend = fallback;
+                    overlay_options.backend = render_backend;
+                    continue;
                 }
+
+                let reason = match source {
+                    libloading::Error::LoadLibraryExW { .. } => {
+                        format!("{:#}", source.source().context("LoadLibraryExW to have a source")?)
+                    }
+                    error => format!("{:#}", error),
+                };
+
+                let controller_error = ControllerError::RenderBackendLoadFailure(render_backend, reason);
+                show_critical_error(&format!(
+                    "{}\n\nFor more help, checkout:\n{}",
+                    controller_error,
+                    controller_error.troubleshooting_link()
+                ));
+                return Ok(());
             }
-            return Ok(());
+            Ok(value) => break value,
         }
-        value => value?,
     };
 
+    cs2.add_metrics_record(
+        obfstr!("render-backend"),
+        &format!("{:?}", render_backend),
+    );
+
     {
         let settings = app_state.resolve::<AppSettings>(())?;
         if let Some(imgui_settings) = &settings.imgui {
@@ -621,12 +1044,29 @@ fn main_overlay() -> anyhow::Result<()> {
         web_radar: Default::default(),
 
         enhancements: vec![
-            Rc::new(RefCell::new(PlayerESP::new())),
-            Rc::new(RefCell::new(SpectatorsListIndicator::new())),
-            Rc::new(RefCell::new(BombInfoIndicator::new())),
-            Rc::new(RefCell::new(TriggerBot::new())),
-            Rc::new(RefCell::new(AntiAimPunsh::new())),
+            NamedEnhancement {
+                name: "PlayerESP",
+                instance: Rc::new(RefCell::new(PlayerESP::new())),
+            },
+            NamedEnhancement {
+                name: "SpectatorsListIndicator",
+                instance: Rc::new(RefCell::new(SpectatorsListIndicator::new())),
+            },
+            NamedEnhancement {
+                name: "BombInfoIndicator",
+                instance: Rc::new(RefCell::new(BombInfoIndicator::new())),
+            },
+            NamedEnhancement {
+                name: "TriggerBot",
+                instance: Rc::new(RefCell::new(TriggerBot::new())),
+            },
+            NamedEnhancement {
+                name: "AntiAimPunsh",
+                instance: Rc::new(RefCell::new(AntiAimPunsh::new())),
+            },
         ],
+        enhancement_timings: Default::default(),
+        last_timings_metric_emit: Cell::new(None),
 
         last_total_read_calls: 0,
         frame_read_calls: 0,
@@ -637,6 +1077,9 @@ fn main_overlay() -> anyhow::Result<()> {
         /* set the screen capture visibility at the beginning of the first update */
         settings_screen_capture_changed: AtomicBool::new(true),
         settings_render_debug_window_changed: AtomicBool::new(true),
+
+        damage_state: Cell::new(DamageState::default()),
+        controller_error: Default::default(),
     };
     let app = Rc::new(RefCell::new(app));
 
@@ -669,30 +1112,49 @@ fn main_overlay() -> anyhow::Result<()> {
         move |ui| {
             let mut app = app.borrow_mut();
 
+            let mut in_timeout = false;
             if let Some((timeout, target)) = &update_timeout {
                 if timeout.elapsed() > *target {
                     update_timeout = None;
                 } else {
-                    /* Not updating. On timeout... */
-                    return true;
+                    in_timeout = true;
                 }
             }
 
-            if let Err(err) = app.update(ui) {
-                if update_fail_count >= 10 {
-                    log::error!("Over 10 errors occurred. Waiting 1s and try again.");
-                    log::error!("Last error: {:#}", err);
-
-                    update_timeout = Some((Instant::now(), Duration::from_millis(1000)));
-                    update_fail_count = 0;
-                    return true;
-                } else {
-                    update_fail_count += 1;
+            if !in_timeout {
+                if let Err(err) = app.update(ui) {
+                    if update_fail_count >= 10 {
+                        log::error!("Over 10 errors occurred. Waiting 1s and try again.");
+                        log::error!("Last error: {:#}", err);
+
+                        if let Some(controller_error) = err
+                            .downcast_ref::<KInterfaceError>()
+                            .and_then(ControllerError::from_kinterface_error)
+                        {
+                            *app.controller_error.borrow_mut() = Some(controller_error);
+                        }
+
+                        update_timeout = Some((Instant::now(), Duration::from_millis(1000)));
+                        update_fail_count = 0;
+                    } else {
+                        update_fail_count += 1;
+                    }
+                } else if app.controller_error.borrow_mut().take().is_some() {
+                    /* the update succeeded again: the error condition has self-healed */
+                    log::info!("Controller recovered from previous error.");
                 }
             }
 
-            app.render(ui);
-            true
+            /* always (attempt to) render, even while in the update timeout, so a */
+            /* persistent controller error stays visible instead of freezing the overlay */
+            let should_redraw = app.should_redraw(ui);
+            if should_redraw {
+                app.render(ui);
+            }
+
+            /* returning false tells the overlay it may sleep until the next input event */
+            /* or the configured minimum refresh interval instead of rendering every frame */
+            should_redraw
         },
     )
 }
@@ -0,0 +1,415 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+};
+
+use anyhow::{
+    Context,
+    Result,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    fonts::{
+        FontDescriptor,
+        FontStyle,
+        GlyphBlock,
+    },
+    Application,
+    RenderBackend,
+    FONT_DEFAULT,
+};
+
+/// Persisted, user-editable controller configuration.
+///
+/// Loaded once at startup via [`load_app_settings`] and written back via
+/// [`save_app_settings`] whenever the app marks itself dirty. Every field
+/// added after the initial release carries `#[serde(default)]` so older
+/// settings files on disk keep loading instead of resetting wholesale.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppSettings {
+    /// Saved ImGui window layout (settings window position/size, ...).
+    #[serde(default)]
+    pub imgui: Option<String>,
+
+    #[serde(default)]
+    pub valthrun_watermark: bool,
+
+    #[serde(default)]
+    pub hide_overlay_from_screen_capture: bool,
+
+    #[serde(default)]
+    pub render_debug_window: bool,
+
+    #[serde(default)]
+    pub metrics: bool,
+
+    #[serde(default)]
+    pub key_settings: KeyToggle,
+
+    /// Named font descriptors, resolved into atlas bytes on startup. The
+    /// [`FONT_DEFAULT`] entry backs both the watermark and any enhancement
+    /// which does not request a specific font.
+    #[serde(default = "default_fonts")]
+    pub fonts: HashMap<String, FontDescriptor>,
+
+    /// Unicode blocks rasterized into the font atlas. Latin is always
+    /// included regardless of this list (see
+    /// [`crate::fonts::glyph_ranges_for`]) since an atlas without it can't
+    /// render anything, including this settings window. Kept short beyond
+    /// that by default since CJK blocks in particular are expensive to
+    /// atlas on low-VRAM systems.
+    #[serde(default = "default_glyph_blocks")]
+    pub glyph_blocks: Vec<GlyphBlock>,
+
+    /// When set, only re-render the overlay when the damage-tracked state
+    /// actually changed (or input/settings visibility forces a redraw),
+    /// instead of unconditionally every frame.
+    #[serde(default)]
+    pub redraw_only_on_change: bool,
+
+    /// Upper bound on redraws per second while idle (no damage, no input).
+    /// `None`/`0` means idle frames are never redrawn: they are skipped entirely.
+    #[serde(default)]
+    pub max_fps: Option<u32>,
+
+    /// Overlay rendering backend to start with. `None` means "auto-detect":
+    /// try Vulkan first and transparently fall back to DirectX/OpenGL if it
+    /// fails to load.
+    #[serde(default)]
+    pub render_backend: Option<RenderBackend>,
+}
+
+fn default_fonts() -> HashMap<String, FontDescriptor> {
+    let mut fonts = HashMap::new();
+    fonts.insert(FONT_DEFAULT.to_string(), FontDescriptor::Embedded);
+    fonts
+}
+
+/// Every settings file, old or new, must at least rasterize Latin — an
+/// atlas without it means no text renders anywhere, including this very
+/// settings window.
+fn default_glyph_blocks() -> Vec<GlyphBlock> {
+    vec![GlyphBlock::Latin]
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            imgui: None,
+            valthrun_watermark: true,
+            hide_overlay_from_screen_capture: false,
+            render_debug_window: false,
+            metrics: true,
+            key_settings: KeyToggle::default(),
+            fonts: default_fonts(),
+            glyph_blocks: default_glyph_blocks(),
+            redraw_only_on_change: false,
+            max_fps: None,
+            render_backend: None,
+        }
+    }
+}
+
+/// Wraps [`imgui::Key`] so it can be persisted: `imgui::Key` itself does not
+/// implement `Serialize`/`Deserialize`.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyToggle(pub imgui::Key);
+
+impl Default for KeyToggle {
+    fn default() -> Self {
+        KeyToggle(imgui::Key::Insert)
+    }
+}
+
+impl Serialize for KeyToggle {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&format!("{:?}", self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyToggle {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        Ok(KeyToggle(key_from_name(&name).unwrap_or(imgui::Key::Insert)))
+    }
+}
+
+fn key_from_name(name: &str) -> Option<imgui::Key> {
+    use imgui::Key::*;
+    Some(match name {
+        "Insert" => Insert,
+        "Delete" => Delete,
+        "Home" => Home,
+        "End" => End,
+        "PageUp" => PageUp,
+        "PageDown" => PageDown,
+        "F1" => F1,
+        "F2" => F2,
+        "F3" => F3,
+        "F4" => F4,
+        "F5" => F5,
+        "F6" => F6,
+        "F7" => F7,
+        "F8" => F8,
+        "F9" => F9,
+        "F10" => F10,
+        "F11" => F11,
+        "F12" => F12,
+        _ => return None,
+    })
+}
+
+fn settings_path() -> Result<PathBuf> {
+    Ok(std::env::current_exe()
+        .context("failed to resolve current executable path")?
+        .with_file_name("config.json"))
+}
+
+/// Loads the persisted settings, falling back to [`AppSettings::default`] if
+/// no settings file exists yet (first run).
+pub fn load_app_settings() -> Result<AppSettings> {
+    let path = settings_path()?;
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Persists the current settings to disk. Called whenever the application
+/// marks its settings dirty (settings window closed, an enhancement changed
+/// one of its own settings, ...).
+pub fn save_app_settings(settings: &AppSettings) -> Result<()> {
+    let path = settings_path()?;
+    let content = serde_json::to_string_pretty(settings)?;
+    std::fs::write(&path, content).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Renders the in-overlay settings window, editing [`AppSettings`] in place
+/// through [`Application::settings_mut`].
+pub struct SettingsUI {
+    new_font_name: String,
+    new_font_source: NewFontSource,
+    new_font_path: String,
+    new_font_path_index: u32,
+    new_font_family: String,
+    new_font_weight: u32,
+    new_font_style: FontStyle,
+    new_font_stretch: u32,
+}
+
+/// Which kind of [`FontDescriptor`] the "add font" form is currently
+/// configured to create.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NewFontSource {
+    Embedded,
+    Path,
+    Family,
+}
+
+impl SettingsUI {
+    pub fn new() -> Self {
+        Self {
+            new_font_name: String::new(),
+            new_font_source: NewFontSource::Embedded,
+            new_font_path: String::new(),
+            new_font_path_index: 0,
+            new_font_family: String::new(),
+            new_font_weight: 400,
+            new_font_style: FontStyle::Normal,
+            new_font_stretch: 5,
+        }
+    }
+
+    pub fn render(&mut self, app: &Application, ui: &imgui::Ui) {
+        ui.window("Valthrun Settings")
+            .size([420.0, 360.0], imgui::Condition::FirstUseEver)
+            .build(|| {
+                let mut settings = app.settings_mut();
+
+                ui.checkbox("Show watermark", &mut settings.valthrun_watermark);
+                ui.checkbox(
+                    "Hide overlay from screen capture",
+                    &mut settings.hide_overlay_from_screen_capture,
+                );
+                ui.checkbox("Render debug window", &mut settings.render_debug_window);
+                ui.checkbox("Share anonymous usage metrics", &mut settings.metrics);
+
+                ui.separator();
+                self.render_font_section(&mut settings, ui);
+
+                ui.separator();
+                self.render_glyph_block_section(&mut settings, ui);
+
+                ui.separator();
+                self.render_redraw_section(&mut settings, ui);
+
+                ui.separator();
+                self.render_backend_section(&mut settings, ui);
+            });
+    }
+
+    /// Lets the user pin a specific rendering backend instead of relying on
+    /// the Vulkan-first auto-detection done at startup (see
+    /// [`crate::RenderBackend::fallback`]). Useful for pre-empting a slow
+    /// failed-load-then-retry cycle on a machine known to lack Vulkan.
+    fn render_backend_section(&mut self, settings: &mut AppSettings, ui: &imgui::Ui) {
+        const BACKENDS: &[(&str, Option<RenderBackend>)] = &[
+            ("Auto-detect", None),
+            ("Vulkan", Some(RenderBackend::Vulkan)),
+            ("DirectX 11", Some(RenderBackend::Dx11)),
+            ("OpenGL", Some(RenderBackend::OpenGl)),
+        ];
+
+        ui.text("Render backend (requires restart)");
+        for (label, backend) in BACKENDS {
+            if ui.radio_button_bool(*label, settings.render_backend == *backend) {
+                settings.render_backend = *backend;
+            }
+        }
+    }
+
+    /// Controls for the damage-driven redraw: whether it's enabled at all,
+    /// and an optional cap on how often an otherwise-idle frame still gets
+    /// redrawn.
+    fn render_redraw_section(&mut self, settings: &mut AppSettings, ui: &imgui::Ui) {
+        ui.text("Rendering");
+        ui.checkbox(
+            "Only redraw on change (reduces idle GPU/CPU usage)",
+            &mut settings.redraw_only_on_change,
+        );
+
+        let mut capped = settings.max_fps.is_some();
+        if ui.checkbox("Cap max FPS", &mut capped) {
+            settings.max_fps = if capped { Some(60) } else { None };
+        }
+
+        if let Some(max_fps) = &mut settings.max_fps {
+            ui.slider("Max FPS", 1, 240, max_fps);
+        }
+    }
+
+    /// One checkbox per selectable, optional Unicode block. Adding a block
+    /// that requires a CJK fallback font (see
+    /// [`GlyphBlock::requires_cjk_fallback`]) costs extra atlas VRAM, so
+    /// each block is opted into individually rather than all-or-nothing.
+    /// Latin itself isn't listed here: [`crate::fonts::glyph_ranges_for`]
+    /// always includes it, so a checkbox for it would be a no-op.
+    fn render_glyph_block_section(&mut self, settings: &mut AppSettings, ui: &imgui::Ui) {
+        const ALL_BLOCKS: &[GlyphBlock] = &[
+            GlyphBlock::LatinExtended,
+            GlyphBlock::Cyrillic,
+            GlyphBlock::ChineseFull,
+            GlyphBlock::Japanese,
+            GlyphBlock::Korean,
+            GlyphBlock::Thai,
+            GlyphBlock::Vietnamese,
+        ];
+
+        ui.text("Glyph ranges");
+        ui.text_disabled("Latin (always included)");
+        for block in ALL_BLOCKS {
+            let mut enabled = settings.glyph_blocks.contains(block);
+            if ui.checkbox(format!("{:?}", block), &mut enabled) {
+                if enabled {
+                    settings.glyph_blocks.push(*block);
+                } else {
+                    settings.glyph_blocks.retain(|b| b != block);
+                }
+            }
+        }
+    }
+
+    /// Lists the currently configured named fonts and lets the user add
+    /// another one, sourced either from the embedded font, a file on disk,
+    /// or a system family resolved through DirectWrite.
+    fn render_font_section(&mut self, settings: &mut AppSettings, ui: &imgui::Ui) {
+        ui.text("Fonts");
+        for (name, descriptor) in settings.fonts.iter() {
+            ui.text(format!("{}: {:?}", name, descriptor));
+        }
+
+        ui.input_text("New font name", &mut self.new_font_name)
+            .build();
+
+        if ui.radio_button_bool("Embedded", self.new_font_source == NewFontSource::Embedded) {
+            self.new_font_source = NewFontSource::Embedded;
+        }
+        ui.same_line();
+        if ui.radio_button_bool("File", self.new_font_source == NewFontSource::Path) {
+            self.new_font_source = NewFontSource::Path;
+        }
+        ui.same_line();
+        if ui.radio_button_bool("System family", self.new_font_source == NewFontSource::Family) {
+            self.new_font_source = NewFontSource::Family;
+        }
+
+        let descriptor = match self.new_font_source {
+            NewFontSource::Embedded => Some(FontDescriptor::Embedded),
+            NewFontSource::Path => {
+                ui.input_text("Font file path", &mut self.new_font_path)
+                    .build();
+                ui.slider("Face index", 0, 15, &mut self.new_font_path_index);
+
+                if self.new_font_path.is_empty() {
+                    None
+                } else {
+                    Some(FontDescriptor::Path {
+                        path: self.new_font_path.clone(),
+                        index: self.new_font_path_index,
+                    })
+                }
+            }
+            NewFontSource::Family => {
+                ui.input_text("Family name", &mut self.new_font_family)
+                    .build();
+                ui.slider("Weight", 100, 900, &mut self.new_font_weight);
+                ui.slider("Stretch", 1, 9, &mut self.new_font_stretch);
+
+                if ui.radio_button_bool("Normal", self.new_font_style == FontStyle::Normal) {
+                    self.new_font_style = FontStyle::Normal;
+                }
+                ui.same_line();
+                if ui.radio_button_bool("Oblique", self.new_font_style == FontStyle::Oblique) {
+                    self.new_font_style = FontStyle::Oblique;
+                }
+                ui.same_line();
+                if ui.radio_button_bool("Italic", self.new_font_style == FontStyle::Italic) {
+                    self.new_font_style = FontStyle::Italic;
+                }
+
+                if self.new_font_family.is_empty() {
+                    None
+                } else {
+                    Some(FontDescriptor::Family {
+                        name: self.new_font_family.clone(),
+                        weight: self.new_font_weight,
+                        style: self.new_font_style,
+                        stretch: self.new_font_stretch,
+                    })
+                }
+            }
+        };
+
+        if ui.button("Add font") && !self.new_font_name.is_empty() {
+            if let Some(descriptor) = descriptor {
+                settings.fonts.insert(self.new_font_name.clone(), descriptor);
+                self.new_font_name.clear();
+                self.new_font_path.clear();
+                self.new_font_family.clear();
+            }
+        }
+    }
+}